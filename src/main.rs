@@ -1,9 +1,259 @@
+use std::hash::Hasher;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use image::{ImageFormat, GenericImageView};
+use image::{DynamicImage, ImageFormat, GenericImageView};
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use twox_hash::XxHash64;
+use usvg::TreeParsing;
+
+/// Bumped whenever the cache key derivation changes, so stale caches from an
+/// older version of climageproc are never mistaken for a hit.
+const CACHE_SCHEMA_VERSION: u8 = 1;
+
+/// Resolved output format, carrying any format-specific encode options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Jpeg(u8),
+    Png,
+    WebP,
+    Gif,
+}
+
+impl Format {
+    /// Resolve a `--format` argument against the source path and `--quality`.
+    ///
+    /// `"auto"` inspects the source extension: JPEG/WebP sources (lossy) stay
+    /// `Jpeg`, while PNG/GIF sources (lossless) stay `Png`.
+    fn from_args(source: &Path, format: &str, quality: u8) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Ok(Format::Jpeg(quality)),
+            "png" => Ok(Format::Png),
+            "webp" => Ok(Format::WebP),
+            "gif" => Ok(Format::Gif),
+            "auto" => {
+                let ext = source
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .map(|ext| ext.to_lowercase())
+                    .unwrap_or_default();
+                match ext.as_str() {
+                    "jpg" | "jpeg" | "webp" => Ok(Format::Jpeg(quality)),
+                    "png" | "gif" => Ok(Format::Png),
+                    _ => Err(anyhow::anyhow!("Cannot infer auto format for: {}", source.display())),
+                }
+            }
+            _ => Err(anyhow::anyhow!("Unsupported format: {}", format)),
+        }
+    }
+
+    /// Infer a `Format` from `path`'s extension, for commands (like `Solid`)
+    /// that have no source image to inspect.
+    fn from_extension(path: &Path, quality: u8) -> Result<Self> {
+        let ext = path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).unwrap_or_default();
+        match ext.as_str() {
+            "jpg" | "jpeg" => Ok(Format::Jpeg(quality)),
+            "png" => Ok(Format::Png),
+            "webp" => Ok(Format::WebP),
+            "gif" => Ok(Format::Gif),
+            _ => Err(anyhow::anyhow!("Cannot infer format from output path: {}", path.display())),
+        }
+    }
+}
+
+/// Save `img` to `output_path` using `format`'s encoder (and quality, for
+/// JPEG).
+fn save_with_format(img: &DynamicImage, output_path: &Path, format: Format) -> Result<()> {
+    match format {
+        Format::Jpeg(quality) => {
+            if !(1..=100).contains(&quality) {
+                return Err(anyhow::anyhow!("quality must be in [1, 100], got {quality}"));
+            }
+            let file = std::fs::File::create(output_path)?;
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(file, quality);
+            img.write_with_encoder(encoder)?;
+        }
+        Format::Png => img.save_with_format(output_path, ImageFormat::Png)?,
+        Format::WebP => img.save_with_format(output_path, ImageFormat::WebP)?,
+        Format::Gif => img.save_with_format(output_path, ImageFormat::Gif)?,
+    }
+    Ok(())
+}
+
+/// A color parsed from a `--color` argument, either opaque RGB or RGBA.
+#[derive(Debug, Clone, Copy)]
+enum Color {
+    Rgb([u8; 3]),
+    Rgba([u8; 4]),
+}
+
+/// Parse a `0xRRGGBB` or `0xRRGGBBAA` hex string into a `Color`.
+fn parse_hex_color(s: &str) -> Result<Color, String> {
+    let hex = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .ok_or_else(|| format!("color must start with 0x, got: {s}"))?;
+
+    match hex.len() {
+        6 => {
+            let v = u32::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
+            Ok(Color::Rgb([(v >> 16) as u8, (v >> 8) as u8, v as u8]))
+        }
+        8 => {
+            let v = u32::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
+            Ok(Color::Rgba([(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, v as u8]))
+        }
+        _ => Err(format!("color must be 0xRRGGBB or 0xRRGGBBAA, got: {s}")),
+    }
+}
+
+/// Serialize the parameters of `command` that affect pixel output, for
+/// mixing into the cache key. Only fields that change `process_image`'s
+/// output need to appear here.
+fn command_params_key(command: &Commands) -> String {
+    match command {
+        Commands::Resize { width, height, mode, .. } => {
+            format!("resize:mode={:?},w={:?},h={:?}", mode, width, height)
+        }
+        Commands::Convert { format, quality, .. } => {
+            format!("convert:format={},quality={}", format, quality)
+        }
+        // Stats/Solid never go through process_directory's cache-key path.
+        Commands::Stats { .. } | Commands::Solid { .. } => unreachable!("no cache key for {command:?}"),
+    }
+}
+
+/// Compute a cache key for `input_path` processed with `command`, combining
+/// the source file's size and mtime (cheap stand-ins for its bytes) with the
+/// serialized command parameters. Returns `None` if the source metadata is
+/// unavailable (the caller will simply not find a cache hit).
+fn cache_key(input_path: &Path, command: &Commands) -> Option<u64> {
+    let metadata = std::fs::metadata(input_path).ok()?;
+    let modified = metadata.modified().ok()?;
+    let mtime = modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write_u64(metadata.len());
+    hasher.write_u64(mtime);
+    hasher.write(command_params_key(command).as_bytes());
+    Some(hasher.finish())
+}
+
+/// Rewrite `output_path` to embed the cache key as a `<16 hex digits><2 hex
+/// digits>` tag before the extension (e.g. `photo.a1b2c3d4e5f6a7b801.jpg`),
+/// mirroring the hash+version tagging scheme used by other batch tools. If
+/// the source metadata can't be read, `output_path` is returned unchanged
+/// and caching is effectively disabled for that file.
+fn tag_with_cache_key(output_path: &Path, input_path: &Path, command: &Commands) -> PathBuf {
+    let Some(key) = cache_key(input_path, command) else {
+        return output_path.to_path_buf();
+    };
+
+    let ext = output_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let stem = output_path.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+    let tagged_name = format!("{stem}.{key:016x}{CACHE_SCHEMA_VERSION:02x}.{ext}");
+
+    output_path.with_file_name(tagged_name)
+}
+
+/// How `--mode` picks a `ResizeOp` once combined with `--width`/`--height`.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum ResizeMode {
+    /// Resize to exact dimensions when both --width and --height are given
+    /// (ignoring aspect ratio), or preserve aspect ratio when only one is
+    /// given. This is the default, matching the tool's original behavior.
+    Scale,
+    /// Resize to fit inside the given box, preserving aspect ratio.
+    Fit,
+    /// Resize to cover the given box, then center-crop to it exactly.
+    Fill,
+}
+
+/// A fully-resolved resize operation, independent of how it was specified
+/// on the command line.
+#[derive(Debug, Clone, Copy)]
+enum ResizeOp {
+    /// Resize to exactly `(w, h)`, ignoring aspect ratio.
+    Scale(u32, u32),
+    /// Resize to width `w`, computing height to preserve aspect ratio.
+    FitWidth(u32),
+    /// Resize to height `h`, computing width to preserve aspect ratio.
+    FitHeight(u32),
+    /// Resize to the largest size that fits inside `(w, h)`, preserving
+    /// aspect ratio; may be smaller than the box on one axis.
+    Fit(u32, u32),
+    /// Resize to cover `(w, h)`, then center-crop to exactly `(w, h)`.
+    Fill(u32, u32),
+}
+
+impl ResizeOp {
+    /// Resolve `--mode`/`--width`/`--height` into a `ResizeOp`.
+    fn from_args(mode: ResizeMode, width: Option<u32>, height: Option<u32>) -> Result<Self> {
+        match (mode, width, height) {
+            (ResizeMode::Scale, Some(w), Some(h)) => Ok(ResizeOp::Scale(w, h)),
+            (ResizeMode::Scale, Some(w), None) => Ok(ResizeOp::FitWidth(w)),
+            (ResizeMode::Scale, None, Some(h)) => Ok(ResizeOp::FitHeight(h)),
+            (ResizeMode::Scale, None, None) => {
+                Err(anyhow::anyhow!("--mode scale requires --width and/or --height"))
+            }
+            (ResizeMode::Fill, Some(w), Some(h)) => Ok(ResizeOp::Fill(w, h)),
+            (ResizeMode::Fill, _, _) => {
+                Err(anyhow::anyhow!("--mode fill requires both --width and --height"))
+            }
+            (ResizeMode::Fit, Some(w), Some(h)) => Ok(ResizeOp::Fit(w, h)),
+            (ResizeMode::Fit, Some(w), None) => Ok(ResizeOp::FitWidth(w)),
+            (ResizeMode::Fit, None, Some(h)) => Ok(ResizeOp::FitHeight(h)),
+            (ResizeMode::Fit, None, None) => {
+                Err(anyhow::anyhow!("--mode fit requires --width and/or --height"))
+            }
+        }
+    }
+
+    /// Apply this operation to `img`, returning the resized (and possibly
+    /// cropped) image.
+    fn apply(&self, img: DynamicImage) -> DynamicImage {
+        match *self {
+            ResizeOp::Scale(w, h) => img.resize_exact(w, h, image::imageops::FilterType::Lanczos3),
+            ResizeOp::FitWidth(w) => {
+                let ratio = img.height() as f32 / img.width() as f32;
+                let h = (w as f32 * ratio).round() as u32;
+                img.resize(w, h, image::imageops::FilterType::Lanczos3)
+            }
+            ResizeOp::FitHeight(h) => {
+                let ratio = img.width() as f32 / img.height() as f32;
+                let w = (h as f32 * ratio).round() as u32;
+                img.resize(w, h, image::imageops::FilterType::Lanczos3)
+            }
+            ResizeOp::Fit(w, h) => img.resize(w, h, image::imageops::FilterType::Lanczos3),
+            ResizeOp::Fill(w, h) => {
+                let (orig_w, orig_h) = img.dimensions();
+                let scale = (w as f64 / orig_w as f64).max(h as f64 / orig_h as f64);
+                let scaled_w = (orig_w as f64 * scale).round() as u32;
+                let scaled_h = (orig_h as f64 * scale).round() as u32;
+
+                let resized = img.resize_exact(scaled_w, scaled_h, image::imageops::FilterType::Lanczos3);
+                let mut buf = resized.to_rgba8();
+                let x = (scaled_w.saturating_sub(w)) / 2;
+                let y = (scaled_h.saturating_sub(h)) / 2;
+                let cropped = image::imageops::crop(&mut buf, x, y, w, h).to_image();
+                DynamicImage::ImageRgba8(cropped)
+            }
+        }
+    }
+}
+
+/// Extensions that directory-walking commands treat as images.
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "svg"];
+
+/// Whether `path`'s extension is one `climageproc` knows how to read.
+fn has_image_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
 
 #[derive(Parser)]
 #[command(name = "climageproc")]
@@ -13,7 +263,7 @@ struct Cli {
     command: Commands,
 }
 
-#[derive(Subcommand)]
+#[derive(Debug, Subcommand)]
 enum Commands {
     /// Resize images while maintaining aspect ratio
     Resize {
@@ -32,8 +282,14 @@ enum Commands {
         /// New height in pixels
         #[arg(short, long)]
         height: Option<u32>,
+
+        /// How to combine width/height into a resize operation. Defaults to
+        /// `scale`, matching the prior behavior of resizing to exact
+        /// dimensions when both --width and --height are given.
+        #[arg(short, long, value_enum, default_value = "scale")]
+        mode: ResizeMode,
     },
-    
+
     /// Convert images to a different format
     Convert {
         /// Input file or directory
@@ -44,33 +300,123 @@ enum Commands {
         #[arg(short, long)]
         output: PathBuf,
         
-        /// Target format (jpg, png, etc.)
+        /// Target format (jpg, png, webp, or auto to match the source's lossy/lossless kind)
         #[arg(short, long)]
         format: String,
+
+        /// JPEG encoder quality, from 1 (worst) to 100 (best); ignored for PNG, WebP and GIF output
+        #[arg(short, long, default_value_t = 85, value_parser = clap::value_parser!(u8).range(1..=100))]
+        quality: u8,
     },
+
+    /// Report aggregate dimension/format stats for a directory of images
+    Stats {
+        /// Directory of images to inspect
+        #[arg(short, long)]
+        input: PathBuf,
+    },
+
+    /// Generate a solid-color placeholder image
+    Solid {
+        /// Fill color as 0xRRGGBB or 0xRRGGBBAA
+        #[arg(short, long, value_parser = parse_hex_color)]
+        color: Color,
+
+        /// Width in pixels
+        #[arg(short, long)]
+        width: u32,
+
+        /// Height in pixels
+        #[arg(short, long)]
+        height: u32,
+
+        /// Output image path (format is inferred from its extension)
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+/// Whether `path` is an SVG source, which `image::open` cannot read.
+fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("svg"))
+        .unwrap_or(false)
 }
 
+/// Rasterize an SVG file into an RGBA `DynamicImage`, sized to `width`/
+/// `height` when given (missing dimensions are filled in to preserve the
+/// SVG's aspect ratio) or to the SVG's intrinsic size otherwise.
+fn rasterize_svg(path: &Path, width: Option<u32>, height: Option<u32>) -> Result<DynamicImage> {
+    let svg_data = std::fs::read(path)
+        .with_context(|| format!("Failed to read SVG: {}", path.display()))?;
+    let tree = usvg::Tree::from_data(&svg_data, &usvg::Options::default())
+        .with_context(|| format!("Failed to parse SVG: {}", path.display()))?;
+
+    let intrinsic = tree.size();
+    let (target_w, target_h) = match (width, height) {
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) => {
+            let ratio = intrinsic.height() / intrinsic.width();
+            (w, (w as f32 * ratio).round().max(1.0) as u32)
+        }
+        (None, Some(h)) => {
+            let ratio = intrinsic.width() / intrinsic.height();
+            ((h as f32 * ratio).round().max(1.0) as u32, h)
+        }
+        (None, None) => (intrinsic.width().round() as u32, intrinsic.height().round() as u32),
+    };
+
+    let mut pixmap = tiny_skia::Pixmap::new(target_w, target_h)
+        .ok_or_else(|| anyhow::anyhow!("Invalid SVG raster size: {target_w}x{target_h}"))?;
+    let transform = tiny_skia::Transform::from_scale(
+        target_w as f32 / intrinsic.width(),
+        target_h as f32 / intrinsic.height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    image::RgbaImage::from_raw(target_w, target_h, pixmap.data().to_vec())
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| anyhow::anyhow!("Failed to build raster image from SVG: {}", path.display()))
+}
+
+/// For commands that save via `image`'s extension-based dispatch (anything
+/// but `Convert`, which picks its encoder explicitly), rewrite an output
+/// path inherited from an SVG source to a concrete raster extension (PNG)
+/// that `image` can actually encode — `.svg` itself isn't a writable format.
+fn ensure_raster_extension(output_path: &Path, input_path: &Path, command: &Commands) -> PathBuf {
+    if is_svg(input_path) && !matches!(command, Commands::Convert { .. }) {
+        output_path.with_extension("png")
+    } else {
+        output_path.to_path_buf()
+    }
+}
+
+/// Load `input_path` as a `DynamicImage`, rasterizing SVG sources and
+/// opening everything else with `image::open`.
+fn load_source_image(input_path: &Path, command: &Commands) -> Result<DynamicImage> {
+    if is_svg(input_path) {
+        let (width, height) = match command {
+            Commands::Resize { width, height, .. } => (*width, *height),
+            _ => (None, None),
+        };
+        rasterize_svg(input_path, width, height)
+    } else {
+        image::open(input_path)
+            .with_context(|| format!("Failed to open image: {}", input_path.display()))
+    }
+}
+
+/// Process `input_path` into `output_path` according to `command`,
+/// overwriting `output_path` if it already exists. Callers that want a
+/// cache-hit skip (i.e. `process_directory`, where `output_path` embeds a
+/// content hash) must check for that themselves before calling this.
 fn process_image(input_path: &Path, output_path: &Path, command: &Commands) -> Result<()> {
-    let img = image::open(input_path)
-        .with_context(|| format!("Failed to open image: {}", input_path.display()))?;
+    let img = load_source_image(input_path, command)?;
 
     let processed_img = match command {
-        Commands::Resize { width, height, .. } => {
-            if let (Some(w), Some(h)) = (width, height) {
-                img.resize_exact(*w, *h, image::imageops::FilterType::Lanczos3)
-            } else if let Some(w) = width {
-                // Calculate height to maintain aspect ratio
-                let ratio = img.height() as f32 / img.width() as f32;
-                let h = (*w as f32 * ratio).round() as u32;
-                img.resize(*w, h, image::imageops::FilterType::Lanczos3)
-            } else if let Some(h) = height {
-                // Calculate width to maintain aspect ratio
-                let ratio = img.width() as f32 / img.height() as f32;
-                let w = (*h as f32 * ratio).round() as u32;
-                img.resize(w, *h, image::imageops::FilterType::Lanczos3)
-            } else {
-                img
-            }
+        Commands::Resize { width, height, mode, .. } => {
+            ResizeOp::from_args(*mode, *width, *height)?.apply(img)
         }
         Commands::Convert { .. } => img,
     };
@@ -81,15 +427,9 @@ fn process_image(input_path: &Path, output_path: &Path, command: &Commands) -> R
     }
 
     match command {
-        Commands::Convert { format, .. } => {
-            let format = match format.to_lowercase().as_str() {
-                "jpg" | "jpeg" => ImageFormat::Jpeg,
-                "png" => ImageFormat::Png,
-                "gif" => ImageFormat::Gif,
-                "webp" => ImageFormat::WebP,
-                _ => return Err(anyhow::anyhow!("Unsupported format: {}", format)),
-            };
-            processed_img.save_with_format(output_path, format)?
+        Commands::Convert { format, quality, .. } => {
+            let format = Format::from_args(input_path, format, *quality)?;
+            save_with_format(&processed_img, output_path, format)?
         }
         _ => {
             processed_img.save(output_path)?
@@ -102,12 +442,7 @@ fn process_image(input_path: &Path, output_path: &Path, command: &Commands) -> R
 fn process_directory(input: &Path, output: &Path, command: &Commands) -> Result<()> {
     let entries: Vec<_> = std::fs::read_dir(input)?
         .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.path().extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| matches!(ext.to_lowercase().as_str(), "jpg" | "jpeg" | "png" | "gif" | "webp"))
-                .unwrap_or(false)
-        })
+        .filter(|e| has_image_extension(&e.path()))
         .collect();
 
     let progress_bar = ProgressBar::new(entries.len() as u64);
@@ -117,22 +452,148 @@ fn process_directory(input: &Path, output: &Path, command: &Commands) -> Result<
             .unwrap()
     );
 
+    let cached = AtomicUsize::new(0);
+    let regenerated = AtomicUsize::new(0);
+
     entries.par_iter().try_for_each(|entry| {
         let input_path = entry.path();
         let file_name = input_path.file_name().unwrap();
         let mut output_path = PathBuf::from(output);
         output_path.push(file_name);
+        output_path = ensure_raster_extension(&output_path, &input_path, command);
 
-        if let Commands::Convert { format, .. } = command {
-            output_path.set_extension(format);
+        if let Commands::Convert { format, quality, .. } = command {
+            let ext = match Format::from_args(&input_path, format, *quality)? {
+                Format::Jpeg(_) => "jpg",
+                Format::Png => "png",
+                Format::WebP => "webp",
+                Format::Gif => "gif",
+            };
+            output_path.set_extension(ext);
         }
+        let untagged_path = output_path;
+        let output_path = tag_with_cache_key(&untagged_path, &input_path, command);
 
-        let result = process_image(&input_path, &output_path, command);
+        // An untagged path means no cache key could be computed, so an
+        // existing file there is never treated as a cache hit.
+        if output_path != untagged_path && output_path.exists() {
+            cached.fetch_add(1, Ordering::Relaxed);
+        } else {
+            process_image(&input_path, &output_path, command)?;
+            regenerated.fetch_add(1, Ordering::Relaxed);
+        }
         progress_bar.inc(1);
-        result
+        Ok::<_, anyhow::Error>(())
     })?;
 
-    progress_bar.finish_with_message("Done!");
+    progress_bar.finish_with_message(format!(
+        "Done! {} regenerated, {} cached",
+        regenerated.load(Ordering::Relaxed),
+        cached.load(Ordering::Relaxed)
+    ));
+    Ok(())
+}
+
+/// Bucket an image's pixel area into a rough size class for the `Stats`
+/// summary (thresholds are in megapixels).
+fn size_bucket(width: u32, height: u32) -> &'static str {
+    let megapixels = (width as u64 * height as u64) as f64 / 1_000_000.0;
+    if megapixels < 1.0 {
+        "small"
+    } else if megapixels < 8.0 {
+        "medium"
+    } else {
+        "large"
+    }
+}
+
+/// Walk `input`, cheaply reading each image's dimensions and format (without
+/// a full decode), and print a summary table of the directory's contents.
+fn run_stats(input: &Path) -> Result<()> {
+    let entries: Vec<_> = std::fs::read_dir(input)?
+        .filter_map(|e| e.ok())
+        .filter(|e| has_image_extension(&e.path()))
+        .collect();
+
+    struct ImageStat {
+        format: &'static str,
+        width: u32,
+        height: u32,
+        bytes: u64,
+    }
+
+    let mut stats = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        let path = entry.path();
+        let (format, width, height) = if is_svg(&path) {
+            let svg_data = std::fs::read(&path)
+                .with_context(|| format!("Failed to read SVG: {}", path.display()))?;
+            let tree = usvg::Tree::from_data(&svg_data, &usvg::Options::default())
+                .with_context(|| format!("Failed to parse SVG: {}", path.display()))?;
+            let size = tree.size();
+            ("Svg", size.width().round() as u32, size.height().round() as u32)
+        } else {
+            let reader = image::io::Reader::open(&path)
+                .with_context(|| format!("Failed to open image: {}", path.display()))?
+                .with_guessed_format()
+                .with_context(|| format!("Failed to guess format: {}", path.display()))?;
+            let format = match reader
+                .format()
+                .ok_or_else(|| anyhow::anyhow!("Unknown format: {}", path.display()))?
+            {
+                ImageFormat::Jpeg => "Jpeg",
+                ImageFormat::Png => "Png",
+                ImageFormat::Gif => "Gif",
+                ImageFormat::WebP => "WebP",
+                _ => "Other",
+            };
+            let (width, height) = reader
+                .into_dimensions()
+                .with_context(|| format!("Failed to read dimensions: {}", path.display()))?;
+            (format, width, height)
+        };
+        let bytes = entry.metadata()?.len();
+        stats.push(ImageStat { format, width, height, bytes });
+    }
+
+    if stats.is_empty() {
+        println!("No images found in {}", input.display());
+        return Ok(());
+    }
+
+    let total = stats.len();
+    let total_bytes: u64 = stats.iter().map(|s| s.bytes).sum();
+    let total_width: u64 = stats.iter().map(|s| s.width as u64).sum();
+    let total_height: u64 = stats.iter().map(|s| s.height as u64).sum();
+    let (min_w, min_h) = stats.iter().map(|s| (s.width, s.height)).min_by_key(|(w, h)| *w as u64 * *h as u64).unwrap();
+    let (max_w, max_h) = stats.iter().map(|s| (s.width, s.height)).max_by_key(|(w, h)| *w as u64 * *h as u64).unwrap();
+
+    println!("Total images:   {total}");
+    println!("Total size:     {total_bytes} bytes");
+    println!(
+        "Avg dimensions: {}x{}",
+        total_width / total as u64,
+        total_height / total as u64
+    );
+    println!("Min dimensions: {min_w}x{min_h}");
+    println!("Max dimensions: {max_w}x{max_h}");
+
+    println!("\nBy format:");
+    for format in ["Jpeg", "Png", "Gif", "WebP", "Svg", "Other"] {
+        let count = stats.iter().filter(|s| s.format == format).count();
+        if count > 0 {
+            println!("  {format}: {count}");
+        }
+    }
+
+    println!("\nBy size (small <1MP, medium <8MP, large >=8MP):");
+    for bucket in ["small", "medium", "large"] {
+        let count = stats.iter().filter(|s| size_bucket(s.width, s.height) == bucket).count();
+        if count > 0 {
+            println!("  {bucket}: {count}");
+        }
+    }
+
     Ok(())
 }
 
@@ -144,10 +605,187 @@ fn main() -> Result<()> {
             if input.is_dir() {
                 process_directory(input, output, cmd)?
             } else {
-                process_image(input, output, cmd)?
+                let output = ensure_raster_extension(output, input, cmd);
+                process_image(input, &output, cmd)?;
             }
         }
+        Commands::Stats { input } => run_stats(input)?,
+        Commands::Solid { color, width, height, output } => {
+            let img = match *color {
+                Color::Rgb(rgb) => DynamicImage::ImageRgb8(image::RgbImage::from_pixel(*width, *height, image::Rgb(rgb))),
+                Color::Rgba(rgba) => DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(*width, *height, image::Rgba(rgba))),
+            };
+
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)?
+            }
+
+            let format = Format::from_extension(output, 85)?;
+            save_with_format(&img, output, format)?;
+        }
     }
 
     Ok(())
+}
+
+#[cfg(test)]
+mod cache_key_tests {
+    use super::*;
+
+    fn sample_command(width: Option<u32>, height: Option<u32>) -> Commands {
+        Commands::Resize {
+            input: PathBuf::from("in"),
+            output: PathBuf::from("out"),
+            width,
+            height,
+            mode: ResizeMode::Scale,
+        }
+    }
+
+    fn write_temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("climageproc-test-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn same_input_and_params_hash_identically() {
+        let path = write_temp_file("same.bin", b"hello world");
+        let command = sample_command(Some(100), None);
+
+        let key_a = cache_key(&path, &command).unwrap();
+        let key_b = cache_key(&path, &command).unwrap();
+        assert_eq!(key_a, key_b);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn different_params_hash_differently() {
+        let path = write_temp_file("diff-params.bin", b"hello world");
+
+        let key_a = cache_key(&path, &sample_command(Some(100), None)).unwrap();
+        let key_b = cache_key(&path, &sample_command(Some(200), None)).unwrap();
+        assert_ne!(key_a, key_b);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn different_file_size_hashes_differently() {
+        let path_a = write_temp_file("content-a.bin", b"aaaaaaaaaa");
+        let path_b = write_temp_file("content-b.bin", b"bbbbbbbbbbbbbbb");
+        let command = sample_command(Some(100), None);
+
+        let key_a = cache_key(&path_a, &command).unwrap();
+        let key_b = cache_key(&path_b, &command).unwrap();
+        assert_ne!(key_a, key_b);
+
+        std::fs::remove_file(&path_a).unwrap();
+        std::fs::remove_file(&path_b).unwrap();
+    }
+
+    #[test]
+    fn missing_input_has_no_cache_key() {
+        let missing = PathBuf::from("/nonexistent/climageproc-test-missing.bin");
+        assert!(cache_key(&missing, &sample_command(Some(100), None)).is_none());
+    }
+
+    #[test]
+    fn tagged_path_embeds_hex_key_and_schema_version_before_extension() {
+        let path = write_temp_file("tag.bin", b"tag me");
+        let command = sample_command(Some(100), None);
+
+        let output_path = PathBuf::from("out/photo.jpg");
+        let tagged = tag_with_cache_key(&output_path, &path, &command);
+
+        let tagged_name = tagged.file_name().unwrap().to_str().unwrap();
+        let parts: Vec<&str> = tagged_name.split('.').collect();
+        assert_eq!(parts.len(), 3, "expected stem.tag.ext, got {tagged_name}");
+        assert_eq!(parts[0], "photo");
+        assert_eq!(parts[2], "jpg");
+        assert_eq!(parts[1].len(), 18, "tag should be 16 hex hash digits + 2 hex schema digits");
+        assert!(parts[1].chars().all(|c| c.is_ascii_hexdigit()));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn tagged_path_is_unchanged_when_input_is_missing() {
+        let missing = PathBuf::from("/nonexistent/climageproc-test-missing.bin");
+        let output_path = PathBuf::from("out/photo.jpg");
+        let command = sample_command(Some(100), None);
+
+        assert_eq!(tag_with_cache_key(&output_path, &missing, &command), output_path);
+    }
+}
+
+#[cfg(test)]
+mod resize_op_tests {
+    use super::*;
+
+    #[test]
+    fn scale_mode_uses_exact_dimensions_when_both_given() {
+        let op = ResizeOp::from_args(ResizeMode::Scale, Some(100), Some(50)).unwrap();
+        assert!(matches!(op, ResizeOp::Scale(100, 50)));
+    }
+
+    #[test]
+    fn scale_mode_preserves_aspect_when_only_one_dimension_given() {
+        assert!(matches!(
+            ResizeOp::from_args(ResizeMode::Scale, Some(100), None).unwrap(),
+            ResizeOp::FitWidth(100)
+        ));
+        assert!(matches!(
+            ResizeOp::from_args(ResizeMode::Scale, None, Some(50)).unwrap(),
+            ResizeOp::FitHeight(50)
+        ));
+    }
+
+    #[test]
+    fn scale_mode_requires_at_least_one_dimension() {
+        assert!(ResizeOp::from_args(ResizeMode::Scale, None, None).is_err());
+    }
+
+    #[test]
+    fn fit_mode_resolves_to_the_right_variant() {
+        assert!(matches!(
+            ResizeOp::from_args(ResizeMode::Fit, Some(100), Some(50)).unwrap(),
+            ResizeOp::Fit(100, 50)
+        ));
+        assert!(matches!(
+            ResizeOp::from_args(ResizeMode::Fit, Some(100), None).unwrap(),
+            ResizeOp::FitWidth(100)
+        ));
+        assert!(matches!(
+            ResizeOp::from_args(ResizeMode::Fit, None, Some(50)).unwrap(),
+            ResizeOp::FitHeight(50)
+        ));
+        assert!(ResizeOp::from_args(ResizeMode::Fit, None, None).is_err());
+    }
+
+    #[test]
+    fn fill_mode_requires_both_dimensions() {
+        assert!(matches!(
+            ResizeOp::from_args(ResizeMode::Fill, Some(100), Some(50)).unwrap(),
+            ResizeOp::Fill(100, 50)
+        ));
+        assert!(ResizeOp::from_args(ResizeMode::Fill, Some(100), None).is_err());
+        assert!(ResizeOp::from_args(ResizeMode::Fill, None, Some(50)).is_err());
+        assert!(ResizeOp::from_args(ResizeMode::Fill, None, None).is_err());
+    }
+
+    #[test]
+    fn fit_width_preserves_aspect_ratio() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::new(200, 100));
+        let resized = ResizeOp::FitWidth(100).apply(img);
+        assert_eq!((resized.width(), resized.height()), (100, 50));
+    }
+
+    #[test]
+    fn fill_crops_to_exact_requested_dimensions() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::new(200, 100));
+        let resized = ResizeOp::Fill(50, 50).apply(img);
+        assert_eq!((resized.width(), resized.height()), (50, 50));
+    }
 }
\ No newline at end of file